@@ -7,68 +7,237 @@ use std::{env, fs};
 #[derive(PartialEq, Debug)]
 pub struct Config {
     pub query: String,
-    pub filename: String,
+    pub filenames: Vec<String>,
     pub case_sensitive: bool,
+    pub line_number: bool,
+    pub count: bool,
+    pub invert_match: bool,
+}
+
+/// A single matched line, carrying enough context to be formatted, filtered or
+/// serialized by a caller that embeds minigrep as a library.
+#[derive(PartialEq, Debug)]
+pub struct Match {
+    pub filename: String,
+    pub line_number: usize,
+    pub line: String,
 }
 
 impl Config {
     /// Creates a configuration from command line arguments \
     /// Note that `args: env::Args` wouldn't allow unit testing, therefore generic Iterator is used: `args: T`
-    pub fn new<T>(mut args: T) -> Result<Config, &'static str>
+    ///
+    /// Flags may appear in any order relative to the positional `query` and file names;
+    /// every positional argument after the query is taken as a path to search.
+    /// `-i`/`--ignore-case` forces case-insensitive matching; when it is absent the
+    /// `CASE_INSENSITIVE` environment variable is still honoured as a fallback default.
+    /// Any unrecognised flag is reported in the returned `Err`.
+    pub fn new<T>(mut args: T) -> Result<Config, String>
     where
         T: Iterator<Item = String>,
     {
         args.next();
 
-        let query = match args.next() {
-            Some(arg) => arg,
-            None => return Err("Didn't get a query string"),
-        };
+        let mut ignore_case = false;
+        let mut line_number = false;
+        let mut count = false;
+        let mut invert_match = false;
+        let mut positional = Vec::new();
+
+        for arg in args {
+            match arg.as_str() {
+                "-i" | "--ignore-case" => ignore_case = true,
+                "-n" | "--line-number" => line_number = true,
+                "-c" | "--count" => count = true,
+                "-v" | "--invert-match" => invert_match = true,
+                flag if flag.starts_with('-') => {
+                    return Err(format!("Unknown flag: {}", flag));
+                }
+                _ => positional.push(arg),
+            }
+        }
 
-        let filename = match args.next() {
+        let mut positional = positional.into_iter();
+
+        let query = match positional.next() {
             Some(arg) => arg,
-            None => return Err("Didn't get a file name"),
+            None => return Err("Didn't get a query string".to_string()),
         };
 
-        let case_sensitive = env::var("CASE_INSENSITIVE").is_err();
+        let filenames: Vec<String> = positional.collect();
+        if filenames.is_empty() {
+            return Err("Didn't get a file name".to_string());
+        }
+
+        let case_sensitive = if ignore_case {
+            false
+        } else {
+            env::var("CASE_INSENSITIVE").is_err()
+        };
 
         Ok(Config {
             query,
-            filename,
+            filenames,
             case_sensitive,
+            line_number,
+            count,
+            invert_match,
         })
     }
 }
 
-/// Main function passing query and file content into search functions
+/// Thin wrapper that preserves the command line stdout behaviour \
+/// It delegates all file walking and reading to [`search_all`] and then formats
+/// the returned `Vec<Match>`, honouring the `line_number`, `count` and
+/// `invert_match` modes of `Config`. Matches are grouped by filename, and the
+/// `filename:` prefix is added when matches come from more than one file. In
+/// count-only mode one count is printed per file with at least one matching line;
+/// files without a matching line get no count line, and nothing is printed when
+/// no line matches at all.
 /// # Example
 /// ```
 /// use minigrep::{Config, run};
 /// let config = Config {
-/// filename: "src/test_data/valid.txt".to_string(),
+/// filenames: vec!["src/test_data/valid.txt".to_string()],
 ///     query: "nobody".to_string(),
 ///     case_sensitive: true,
+///     line_number: false,
+///     count: false,
+///     invert_match: false,
 /// };
 /// assert_eq!(run(config).unwrap(), ());
 /// ```
 pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
+    let matches = search_all(&config)?;
+
+    // Group matches by filename, preserving the order they were walked in.
+    let mut groups: Vec<(String, Vec<Match>)> = Vec::new();
+    for m in matches {
+        match groups.iter_mut().find(|(name, _)| name == &m.filename) {
+            Some((_, group)) => group.push(m),
+            None => groups.push((m.filename.clone(), vec![m])),
+        }
+    }
+
+    let show_filename = groups.len() > 1;
+
+    for (filename, group) in groups {
+        if config.count {
+            if show_filename {
+                println!("{}:{}", filename, group.len());
+            } else {
+                println!("{}", group.len());
+            }
+            continue;
+        }
+
+        for m in group {
+            let mut prefix = String::new();
+            if show_filename {
+                prefix.push_str(&format!("{}:", filename));
+            }
+            if config.line_number {
+                prefix.push_str(&format!("{}:", m.line_number));
+            }
+            println!("{}{}", prefix, m.line);
+        }
+    }
+
+    Ok(())
+}
+
+/// Structured entry point for embedding minigrep as a library \
+/// It walks every path in `filenames` exactly like [`run`], but instead of
+/// printing it returns a `Vec<Match>` carrying the filename, 1-based line number
+/// and matched line text, leaving formatting to the caller. Files that cannot be
+/// read are reported to stderr and skipped.
+/// # Example
+/// ```
+/// use minigrep::{Config, search_all};
+/// let config = Config {
+///     filenames: vec!["src/test_data/valid.txt".to_string()],
+///     query: "nobody".to_string(),
+///     case_sensitive: true,
+///     line_number: false,
+///     count: false,
+///     invert_match: false,
+/// };
+/// let matches = search_all(&config).unwrap();
+/// assert!(matches.iter().all(|m| m.filename == "src/test_data/valid.txt"));
+/// ```
+pub fn search_all(config: &Config) -> Result<Vec<Match>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for path in &config.filenames {
+        collect_files(path, &mut files);
+    }
+
+    let mut matches = Vec::new();
+    for file in files {
+        let contents = match fs::read_to_string(&file) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                continue;
+            }
+        };
 
-    let results = if config.case_sensitive {
-        search(&config.query, &contents)
+        for (line_number, line) in search_in(config, &contents) {
+            matches.push(Match {
+                filename: file.clone(),
+                line_number,
+                line: line.to_string(),
+            });
+        }
+    }
+
+    Ok(matches)
+}
+
+/// Dispatches to the case-sensitive or case-insensitive search according to `config`
+fn search_in<'a>(config: &Config, contents: &'a str) -> Vec<(usize, &'a str)> {
+    if config.case_sensitive {
+        search(&config.query, contents, config.invert_match)
     } else {
-        search_case_insensitive(&config.query, &contents)
+        search_case_insensitive(&config.query, contents, config.invert_match)
+    }
+}
+
+/// Recursively collect every file reachable from `path` into `files` \
+/// Directories are descended with `std::fs::read_dir`; paths that cannot be
+/// inspected are reported to stderr and skipped rather than aborting the walk.
+fn collect_files(path: &str, files: &mut Vec<String>) {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return;
+        }
     };
 
-    for line in results {
-        println!("{}", line);
+    if !metadata.is_dir() {
+        files.push(path.to_string());
+        return;
     }
 
-    Ok(())
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return;
+        }
+    };
+
+    for entry in entries {
+        match entry {
+            Ok(entry) => collect_files(&entry.path().to_string_lossy(), files),
+            Err(e) => eprintln!("{}: {}", path, e),
+        }
+    }
 }
 
 /// Case sensitive substring search for query in contents \
-/// It prints matching lines
+/// Each matching line is paired with its 1-based line number. When `invert` is
+/// set the lines that do NOT contain the query are returned instead.
 /// # Example
 /// ```
 /// use minigrep::search;
@@ -79,17 +248,20 @@ pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
 ///     Pick three.\n\
 ///     Duct tape.";
 ///
-/// assert_eq!(search(query, contents), vec!["safe, fast, productive."]);
+/// assert_eq!(search(query, contents, false), vec![(2, "safe, fast, productive.")]);
 /// ```
-pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+pub fn search<'a>(query: &str, contents: &'a str, invert: bool) -> Vec<(usize, &'a str)> {
     contents
         .lines()
-        .filter(|line| line.contains(query))
+        .enumerate()
+        .filter(|(_, line)| line.contains(query) != invert)
+        .map(|(i, line)| (i + 1, line))
         .collect()
 }
 
 /// Case insensitive substring search for query in contents \
-/// It prints matching lines
+/// Each matching line is paired with its 1-based line number. When `invert` is
+/// set the lines that do NOT contain the query are returned instead.
 /// # Example
 /// ```
 /// use minigrep::search_case_insensitive;
@@ -101,15 +273,21 @@ pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
 ///    Trust me.";
 ///
 /// assert_eq!(
-///     search_case_insensitive(query, contents),
-///     vec!["Rust:", "Trust me."]
+///     search_case_insensitive(query, contents, false),
+///     vec![(1, "Rust:"), (4, "Trust me.")]
 /// );
 /// ```
-pub fn search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+pub fn search_case_insensitive<'a>(
+    query: &str,
+    contents: &'a str,
+    invert: bool,
+) -> Vec<(usize, &'a str)> {
     let query = query.to_lowercase();
     contents
         .lines()
-        .filter(|line| line.to_lowercase().contains(&query))
+        .enumerate()
+        .filter(|(_, line)| line.to_lowercase().contains(&query) != invert)
+        .map(|(i, line)| (i + 1, line))
         .collect()
 }
 
@@ -127,8 +305,11 @@ mod tests {
             Config::new(args),
             Ok(Config {
                 query: "nobody".to_string(),
-                filename: "poem.txt".to_string(),
-                case_sensitive: true
+                filenames: vec!["poem.txt".to_string()],
+                case_sensitive: true,
+                line_number: false,
+                count: false,
+                invert_match: false
             })
         );
     }
@@ -138,52 +319,190 @@ mod tests {
         let vec: Vec<_> = vec!["minigrep".to_string()];
         assert_eq!(
             Config::new(vec.into_iter()),
-            Err("Didn't get a query string")
+            Err("Didn't get a query string".to_string())
         );
     }
 
     #[test]
     fn config_new_missing_file_name() {
         let vec: Vec<_> = vec!["minigrep".to_string(), "nobody".to_string()];
-        assert_eq!(Config::new(vec.into_iter()), Err("Didn't get a file name"));
+        assert_eq!(
+            Config::new(vec.into_iter()),
+            Err("Didn't get a file name".to_string())
+        );
+    }
+
+    #[test]
+    fn config_new_ignore_case_flag() {
+        let args = ["minigrep", "-i", "nobody", "poem.txt"]
+            .iter()
+            .map(|s| s.to_string());
+        assert_eq!(
+            Config::new(args),
+            Ok(Config {
+                query: "nobody".to_string(),
+                filenames: vec!["poem.txt".to_string()],
+                case_sensitive: false,
+                line_number: false,
+                count: false,
+                invert_match: false
+            })
+        );
+    }
+
+    #[test]
+    fn config_new_flag_after_positionals() {
+        let args = ["minigrep", "nobody", "poem.txt", "--ignore-case"]
+            .iter()
+            .map(|s| s.to_string());
+        assert_eq!(
+            Config::new(args),
+            Ok(Config {
+                query: "nobody".to_string(),
+                filenames: vec!["poem.txt".to_string()],
+                case_sensitive: false,
+                line_number: false,
+                count: false,
+                invert_match: false
+            })
+        );
+    }
+
+    #[test]
+    fn config_new_unknown_flag() {
+        let args = ["minigrep", "-x", "nobody", "poem.txt"]
+            .iter()
+            .map(|s| s.to_string());
+        assert_eq!(Config::new(args), Err("Unknown flag: -x".to_string()));
     }
 
     #[test]
     fn run_file_not_found() {
         let config = Config {
-            filename: "file_not_found.txt".to_string(),
+            filenames: vec!["file_not_found.txt".to_string()],
             query: "nobody".to_string(),
             case_sensitive: true,
+            line_number: false,
+            count: false,
+            invert_match: false,
         };
 
+        // A missing path is reported to stderr and skipped, so the run completes
+        // with no matches rather than aborting.
+        assert_eq!(search_all(&config).unwrap(), vec![]);
+        assert_eq!(run(config).unwrap(), ());
+    }
+
+    #[test]
+    fn run_invalid_content() {
+        let config = Config {
+            filenames: vec!["src/test_data/invalid.txt".to_string()],
+            query: "nobody".to_string(),
+            case_sensitive: true,
+            line_number: false,
+            count: false,
+            invert_match: false,
+        };
+
+        // Non-UTF-8 content is reported to stderr and skipped, not fatal.
+        assert_eq!(search_all(&config).unwrap(), vec![]);
+        assert_eq!(run(config).unwrap(), ());
+    }
+
+    #[test]
+    fn run_valid_content() {
+        let config = Config {
+            filenames: vec!["src/test_data/valid.txt".to_string()],
+            query: "nobody".to_string(),
+            case_sensitive: true,
+            line_number: false,
+            count: false,
+            invert_match: false,
+        };
+        assert_eq!(run(config).unwrap(), ());
+    }
+
+    #[test]
+    fn search_all_valid_content() {
+        let config = Config {
+            filenames: vec!["src/test_data/valid.txt".to_string()],
+            query: "nobody".to_string(),
+            case_sensitive: true,
+            line_number: false,
+            count: false,
+            invert_match: false,
+        };
+
+        let matches = search_all(&config).unwrap();
+        assert!(!matches.is_empty());
+        assert!(matches
+            .iter()
+            .all(|m| m.filename == "src/test_data/valid.txt" && m.line.contains("nobody")));
+    }
+
+    #[test]
+    fn collect_files_recurses_directory() {
+        let mut files = Vec::new();
+        collect_files("src/test_data/tree", &mut files);
+        files.sort();
         assert_eq!(
-            run(config).unwrap_err().to_string(),
-            "No such file or directory (os error 2)"
+            files,
+            vec![
+                "src/test_data/tree/a.txt".to_string(),
+                "src/test_data/tree/sub/b.txt".to_string(),
+            ]
         );
     }
 
     #[test]
-    fn run_invalid_content() {
+    fn search_all_recurses_and_tags_each_file() {
         let config = Config {
-            filename: "src/test_data/invalid.txt".to_string(),
+            filenames: vec!["src/test_data/tree".to_string()],
             query: "nobody".to_string(),
             case_sensitive: true,
+            line_number: false,
+            count: false,
+            invert_match: false,
         };
 
+        // Every file under the directory is searched and each match carries the
+        // path it came from, which is what `run` turns into the `filename:` prefix.
+        let mut filenames: Vec<_> = search_all(&config)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.filename)
+            .collect();
+        filenames.sort();
+        filenames.dedup();
         assert_eq!(
-            run(config).unwrap_err().to_string(),
-            "stream did not contain valid UTF-8"
+            filenames,
+            vec![
+                "src/test_data/tree/a.txt".to_string(),
+                "src/test_data/tree/sub/b.txt".to_string(),
+            ]
         );
     }
 
     #[test]
-    fn run_valid_content() {
+    fn search_all_skips_unreadable_path_and_keeps_going() {
         let config = Config {
-            filename: "src/test_data/valid.txt".to_string(),
+            filenames: vec![
+                "file_not_found.txt".to_string(),
+                "src/test_data/valid.txt".to_string(),
+            ],
             query: "nobody".to_string(),
             case_sensitive: true,
+            line_number: false,
+            count: false,
+            invert_match: false,
         };
-        assert_eq!(run(config).unwrap(), ());
+
+        // The missing path is skipped; the readable file still yields its matches.
+        let matches = search_all(&config).unwrap();
+        assert!(!matches.is_empty());
+        assert!(matches
+            .iter()
+            .all(|m| m.filename == "src/test_data/valid.txt"));
     }
 
     #[test]
@@ -194,10 +513,13 @@ mod tests {
         safe, fast, productive.\n\
         Pick three.";
 
-        assert_eq!(search(query, contents), vec![] as Vec<&str>);
+        assert_eq!(search(query, contents, false), vec![] as Vec<(usize, &str)>);
         // Other syntax alternatives
-        assert_eq!(search(query, contents), <Vec<&str>>::new());
-        assert_eq!(search(query, contents), Vec::new() as Vec<&str>);
+        assert_eq!(search(query, contents, false), <Vec<(usize, &str)>>::new());
+        assert_eq!(
+            search(query, contents, false),
+            Vec::new() as Vec<(usize, &str)>
+        );
     }
 
     #[test]
@@ -209,7 +531,10 @@ mod tests {
         Pick three.\n\
         Duct tape.";
 
-        assert_eq!(search(query, contents), vec!["safe, fast, productive."]);
+        assert_eq!(
+            search(query, contents, false),
+            vec![(2, "safe, fast, productive.")]
+        );
     }
 
     #[test]
@@ -221,11 +546,22 @@ mod tests {
             Pick three.";
 
         assert_eq!(
-            search(query, contents),
-            vec!["Rust:", "safe, fast, productive."]
+            search(query, contents, false),
+            vec![(1, "Rust:"), (2, "safe, fast, productive.")]
         );
     }
 
+    #[test]
+    fn search_invert_match() {
+        let query = "st";
+        let contents = "\
+            Rust:\n\
+            safe, fast, productive.\n\
+            Pick three.";
+
+        assert_eq!(search(query, contents, true), vec![(3, "Pick three.")]);
+    }
+
     #[test]
     fn search_case_insensitive() {
         let query = "rUsT";
@@ -236,8 +572,8 @@ mod tests {
         Trust me.";
 
         assert_eq!(
-            super::search_case_insensitive(query, contents),
-            vec!["Rust:", "Trust me."]
+            super::search_case_insensitive(query, contents, false),
+            vec![(1, "Rust:"), (4, "Trust me.")]
         );
     }
 }
@@ -6,6 +6,7 @@ use minigrep::*;
 /// How to run:
 /// ```bash
 /// $ cargo run nobody poem.txt
+/// $ cargo run -- -i are poem.txt
 /// $ CASE_INSENSITIVE=true cargo run are poem.txt`
 /// ```
 fn main() {